@@ -1,12 +1,885 @@
 use colored::Colorize;
 use fs_extra::dir::create_all;
+use git2::Repository;
+use std::collections::{HashMap, VecDeque};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Mutex;
+use std::thread;
 
-use crate::project::CorkConfig;
+use crate::lock;
+use crate::project::{CorkConfig, Dependency};
 
-pub fn build_project(release: bool) -> Result<PathBuf, String> {
+// Toolchain and flags resolved for a build, merging [build] and the active
+// [profile.debug]/[profile.release] section with CC/CFLAGS overrides.
+#[derive(Clone)]
+struct ResolvedProfile {
+    compiler: String,
+    opt_flag: Option<String>,
+    debug_symbols: bool,
+    cflags: Vec<String>,
+    ldflags: Vec<String>,
+}
+
+fn resolve_profile(config: &CorkConfig, release: bool) -> ResolvedProfile {
+    let profile = if release {
+        &config.profile.release
+    } else {
+        &config.profile.debug
+    };
+
+    let compiler = std::env::var("CC")
+        .ok()
+        .or_else(|| config.build.compiler.clone())
+        .unwrap_or_else(|| "gcc".to_string());
+
+    let opt_flag = profile
+        .opt_level
+        .clone()
+        .or_else(|| release.then(|| "3".to_string()))
+        .map(|level| format!("-O{level}"));
+
+    let debug_symbols = profile.debug_symbols.unwrap_or(!release);
+
+    let cflags = if let Ok(env_cflags) = std::env::var("CFLAGS") {
+        env_cflags.split_whitespace().map(String::from).collect()
+    } else {
+        let mut cflags = config.build.cflags.clone();
+        cflags.extend(profile.cflags.iter().cloned());
+        cflags
+    };
+
+    let mut ldflags = config.build.ldflags.clone();
+    ldflags.extend(profile.ldflags.iter().cloned());
+
+    ResolvedProfile {
+        compiler,
+        opt_flag,
+        debug_symbols,
+        cflags,
+        ldflags,
+    }
+}
+
+#[cfg(test)]
+mod profile_tests {
+    use super::*;
+    use crate::project::{BuildConfig, CorkConfig, ProfileConfig, ProfilesConfig, ProjectConfig};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    // CC/CFLAGS are process-wide state; cargo runs tests concurrently on
+    // multiple threads, so mutating them needs this lock to keep the tests
+    // in this module from stepping on each other.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    fn config_with(build: BuildConfig, debug: ProfileConfig) -> CorkConfig {
+        CorkConfig {
+            project: ProjectConfig {
+                name: "test".to_string(),
+                version: "0.1.0".to_string(),
+            },
+            dependencies: HashMap::new(),
+            build,
+            profile: ProfilesConfig {
+                debug,
+                release: ProfileConfig::default(),
+            },
+        }
+    }
+
+    #[test]
+    fn cflags_env_var_replaces_configured_cflags() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let config = config_with(
+            BuildConfig {
+                compiler: None,
+                cflags: vec!["-Wall".to_string()],
+                ldflags: vec![],
+            },
+            ProfileConfig {
+                cflags: vec!["-Wextra".to_string()],
+                ..ProfileConfig::default()
+            },
+        );
+
+        std::env::set_var("CFLAGS", "-DFOO");
+        let profile = resolve_profile(&config, false);
+        std::env::remove_var("CFLAGS");
+
+        assert_eq!(profile.cflags, vec!["-DFOO".to_string()]);
+    }
+
+    #[test]
+    fn cc_env_var_overrides_configured_compiler() {
+        let _guard = ENV_LOCK.lock().unwrap();
+        let config = config_with(
+            BuildConfig {
+                compiler: Some("clang".to_string()),
+                cflags: vec![],
+                ldflags: vec![],
+            },
+            ProfileConfig::default(),
+        );
+
+        std::env::set_var("CC", "tcc");
+        let profile = resolve_profile(&config, false);
+        std::env::remove_var("CC");
+
+        assert_eq!(profile.compiler, "tcc");
+    }
+}
+
+// A single .c -> .o compilation to be dispatched to the worker pool.
+struct CompileJob {
+    src_file: PathBuf,
+    obj_file: PathBuf,
+    include_dirs: Vec<PathBuf>,
+    profile: ResolvedProfile,
+}
+
+impl CompileJob {
+    fn run(&self) -> Result<(), String> {
+        let dep_file = self.obj_file.with_extension("d");
+        let mut cmd = Command::new(&self.profile.compiler);
+        cmd.arg("-c")
+            .arg(&self.src_file)
+            .arg("-o")
+            .arg(&self.obj_file)
+            .arg("-MMD")
+            .arg("-MP")
+            .arg("-MF")
+            .arg(&dep_file);
+        for inc in &self.include_dirs {
+            cmd.arg("-I").arg(inc);
+        }
+        if self.profile.debug_symbols {
+            cmd.arg("-g");
+        }
+        if let Some(opt_flag) = &self.profile.opt_flag {
+            cmd.arg(opt_flag);
+        }
+        for flag in &self.profile.cflags {
+            cmd.arg(flag);
+        }
+
+        let output = cmd
+            .output()
+            .map_err(|e| format!("Failed to compile {:?}: {e}", self.src_file))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!(
+                "Compilation failed for {:?}:\n{stderr}",
+                self.src_file
+            ));
+        }
+        Ok(())
+    }
+}
+
+// Default worker count: one per logical CPU.
+fn default_job_count() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+// Runs every job across a bounded pool of job_count worker threads,
+// collecting every compile failure instead of stopping at the first one.
+fn compile_all(jobs: Vec<CompileJob>, job_count: usize) -> Result<(), String> {
+    if jobs.is_empty() {
+        return Ok(());
+    }
+
+    let queue: Mutex<VecDeque<CompileJob>> = Mutex::new(jobs.into());
+    let errors: Mutex<Vec<String>> = Mutex::new(Vec::new());
+    let worker_count = job_count.max(1);
+
+    thread::scope(|scope| {
+        for _ in 0..worker_count {
+            scope.spawn(|| loop {
+                let job = queue.lock().unwrap().pop_front();
+                let Some(job) = job else { break };
+                if let Err(e) = job.run() {
+                    errors.lock().unwrap().push(e);
+                }
+            });
+        }
+    });
+
+    let errors = errors.into_inner().unwrap();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod compile_pool_tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn broken_job(dir: &Path, name: &str) -> CompileJob {
+        let src_file = dir.join(format!("{name}.c"));
+        fs::write(&src_file, "this is not valid C\n").unwrap();
+        CompileJob {
+            src_file,
+            obj_file: dir.join(format!("{name}.o")),
+            include_dirs: vec![],
+            profile: ResolvedProfile {
+                compiler: "gcc".to_string(),
+                opt_flag: None,
+                debug_symbols: false,
+                cflags: vec![],
+                ldflags: vec![],
+            },
+        }
+    }
+
+    #[test]
+    fn compile_all_collects_every_failure_not_just_the_first() {
+        let dir = tempdir().expect("Failed to create temporary directory");
+        let jobs = vec![broken_job(dir.path(), "a"), broken_job(dir.path(), "b")];
+
+        let result = compile_all(jobs, 2);
+
+        let err = result.expect_err("Expected compilation to fail");
+        assert!(err.contains("a.c"), "missing failure for a.c: {err}");
+        assert!(err.contains("b.c"), "missing failure for b.c: {err}");
+    }
+}
+
+// Per-user cache directory a git dependency is cloned into, keyed by a hash
+// of the URL *and* the resolved target ref so two dependents pinned to
+// different refs of the same repo get separate working directories instead
+// of clobbering one shared checkout.
+fn git_cache_dir(url: &str, target: &str) -> Result<PathBuf, String> {
+    let home = std::env::var("HOME")
+        .map_err(|_| "Failed to locate home directory: `HOME` is not set".to_string())?;
+
+    Ok(Path::new(&home)
+        .join(".cork")
+        .join("git")
+        .join(lock::hash_hex(&format!("{url}#{target}"))))
+}
+
+fn git_commit_hash(dir: &Path) -> Result<String, String> {
+    let repo = Repository::open(dir)
+        .map_err(|e| format!("Failed to open repository at {}: {e}", dir.display()))?;
+    let head = repo
+        .head()
+        .map_err(|e| format!("Failed to read HEAD at {}: {e}", dir.display()))?;
+    let commit = head
+        .peel_to_commit()
+        .map_err(|e| format!("Failed to resolve HEAD commit at {}: {e}", dir.display()))?;
+    Ok(commit.id().to_string())
+}
+
+// Clones (or fetches, if already cached) a git dependency into its cache
+// directory, checks out the requested `rev`/`tag`/`branch` (preferring a
+// `rev` over a `tag` over a `branch`, and falling back to the remote's
+// default branch), and returns the checked-out working tree's path plus the
+// resolved target ref. `rev` should be the `Cork.lock`-pinned commit when
+// one is available, so repeat builds check out exactly the same revision.
+fn resolve_git_dependency(
+    dep_name: &str,
+    url: &str,
+    branch: Option<&str>,
+    tag: Option<&str>,
+    rev: Option<&str>,
+) -> Result<(PathBuf, String), String> {
+    let target = rev
+        .map(|r| r.to_string())
+        .or_else(|| tag.map(|t| format!("refs/tags/{t}")))
+        .or_else(|| branch.map(|b| format!("origin/{b}")))
+        .unwrap_or_else(|| "origin/HEAD".to_string());
+
+    let cache_dir = git_cache_dir(url, &target)?;
+
+    let repo = if cache_dir.join(".git").exists() {
+        let repo = Repository::open(&cache_dir).map_err(|e| {
+            format!("Failed to open cached repository for dependency `{dep_name}`: {e}")
+        })?;
+        repo.find_remote("origin")
+            .and_then(|mut remote| remote.fetch(&[] as &[&str], None, None))
+            .map_err(|e| format!("Failed to fetch dependency `{dep_name}`: {e}"))?;
+        repo
+    } else {
+        create_all(&cache_dir, false)
+            .map_err(|e| format!("Failed to create git cache directory for `{dep_name}`: {e}"))?;
+        Repository::clone(url, &cache_dir)
+            .map_err(|e| format!("Failed to clone dependency `{dep_name}` from `{url}`: {e}"))?
+    };
+
+    let object = repo
+        .revparse_single(&target)
+        .map_err(|e| format!("Failed to resolve ref `{target}` for dependency `{dep_name}`: {e}"))?;
+
+    repo.checkout_tree(&object, None)
+        .map_err(|e| format!("Failed to checkout dependency `{dep_name}`: {e}"))?;
+    repo.set_head_detached(object.id())
+        .map_err(|e| format!("Failed to set HEAD for dependency `{dep_name}`: {e}"))?;
+
+    Ok((cache_dir, target))
+}
+
+// Resolves a Dependency to the local directory its Cork.toml/src/include
+// live in, optionally pinning a git dependency to pinned_rev (the commit
+// recorded in Cork.lock) instead of re-resolving its rev/tag/branch. A path
+// dependency is resolved relative to base_dir (the directory of the
+// Cork.toml that declared it), not the process's current directory, so
+// transitive path dependencies keep working regardless of where `cork` was
+// invoked from. Returns the directory plus, for git dependencies, the
+// source URL and the commit actually checked out. git_targets records the
+// ref every git URL has resolved to so far in this graph, so a second
+// dependent asking for a different ref of the same URL is rejected instead
+// of silently using whichever ref happened to resolve last.
+fn resolve_node(
+    dep_name: &str,
+    dep: &Dependency,
+    base_dir: &Path,
+    pinned_rev: Option<&str>,
+    git_targets: &mut HashMap<String, String>,
+) -> Result<(PathBuf, Option<String>, Option<String>), String> {
+    match dep {
+        Dependency::Path { path } => Ok((base_dir.join(path), None, None)),
+        Dependency::Git {
+            git,
+            branch,
+            tag,
+            rev,
+        } => {
+            let rev = pinned_rev.or(rev.as_deref());
+            let (dir, target) =
+                resolve_git_dependency(dep_name, git, branch.as_deref(), tag.as_deref(), rev)?;
+
+            if let Some(existing) = git_targets.get(git) {
+                if existing != &target {
+                    return Err(format!(
+                        "error: dependency `{dep_name}` resolves `{git}` at `{target}`, but \
+                         another dependent already resolved it at `{existing}` earlier in the graph"
+                    ));
+                }
+            } else {
+                git_targets.insert(git.clone(), target.clone());
+            }
+
+            let commit = git_commit_hash(&dir)?;
+            Ok((dir, Some(git.clone()), Some(commit)))
+        }
+    }
+}
+
+#[cfg(test)]
+mod git_dependency_tests {
+    use super::*;
+    use std::process::Stdio;
+    use tempfile::tempdir;
+
+    // Creates a local repo with one commit on `main` and a `v1` tag, so
+    // tests can resolve git dependencies without reaching the network.
+    fn make_source_repo(dir: &Path) {
+        let run = |args: &[&str]| {
+            Command::new("git")
+                .args(args)
+                .current_dir(dir)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .expect("Failed to run git")
+        };
+
+        assert!(run(&["init", "-q", "-b", "main"]).success());
+        assert!(run(&["config", "user.email", "test@example.com"]).success());
+        assert!(run(&["config", "user.name", "Test"]).success());
+        fs::write(dir.join("Cork.toml"), "[project]\nname = \"dep\"\nversion = \"0.1.0\"\n")
+            .unwrap();
+        assert!(run(&["add", "-A"]).success());
+        assert!(run(&["commit", "-q", "-m", "initial"]).success());
+        assert!(run(&["tag", "v1"]).success());
+    }
+
+    #[test]
+    fn resolving_different_refs_of_the_same_url_does_not_share_a_checkout() {
+        let home = tempdir().expect("Failed to create temporary HOME");
+        let source = tempdir().expect("Failed to create temporary directory");
+        make_source_repo(source.path());
+
+        std::env::set_var("HOME", home.path());
+        let url = format!("file://{}", source.path().display());
+
+        let (dir_v1, target_v1) =
+            resolve_git_dependency("dep", &url, None, Some("v1"), None).unwrap();
+        let (dir_main, target_main) =
+            resolve_git_dependency("dep", &url, Some("main"), None, None).unwrap();
+
+        assert_ne!(target_v1, target_main);
+        assert_ne!(
+            dir_v1, dir_main,
+            "distinct refs of the same URL must not share a cache directory"
+        );
+    }
+}
+
+// One node of the resolved transitive dependency graph.
+#[derive(Debug)]
+struct ResolvedNode {
+    name: String,
+    dir: PathBuf,
+    // The path as declared in Cork.toml, for path dependencies. Recorded
+    // separately from `dir` (which is canonicalized for dedup) so Cork.lock
+    // stores a portable path instead of a machine-specific absolute one.
+    declared_path: Option<String>,
+    git_url: Option<String>,
+    commit: Option<String>,
+}
+
+// Recursively resolves `dep`, then its own Cork.toml dependencies,
+// depth-first, appending each node to `order` only after its dependencies
+// (so `order` ends up in build order) and deduplicating by resolved
+// directory. `base_dir` is the directory of the Cork.toml declaring `dep`,
+// so a path dependency resolves relative to its declarer rather than the
+// process's current directory. `chain` tracks the resolved directory of
+// every dependency on the current path from the root (not just its
+// caller-chosen alias, since two unrelated dependencies can share a generic
+// alias like `utils`), used to report the full cycle by name if one is
+// found.
+fn resolve_graph_recursive(
+    name: &str,
+    dep: &Dependency,
+    base_dir: &Path,
+    pins: &HashMap<String, String>,
+    git_targets: &mut HashMap<String, String>,
+    chain: &mut Vec<(String, PathBuf)>,
+    visited: &mut HashMap<PathBuf, ResolvedNode>,
+    order: &mut Vec<PathBuf>,
+) -> Result<(), String> {
+    let pinned_rev = match dep {
+        Dependency::Git { git, .. } => pins.get(git).map(String::as_str),
+        Dependency::Path { .. } => None,
+    };
+
+    let (dir, git_url, commit) = resolve_node(name, dep, base_dir, pinned_rev, git_targets)?;
+    let canonical = dir.canonicalize().unwrap_or(dir);
+
+    if let Some(pos) = chain.iter().position(|(_, p)| *p == canonical) {
+        let mut names: Vec<String> = chain[pos..].iter().map(|(n, _)| n.clone()).collect();
+        names.push(name.to_string());
+        return Err(format!("Dependency cycle detected: {}", names.join(" -> ")));
+    }
+
+    if visited.contains_key(&canonical) {
+        return Ok(());
+    }
+
+    chain.push((name.to_string(), canonical.clone()));
+
+    let dep_cork_toml = canonical.join("Cork.toml");
+    if !dep_cork_toml.exists() {
+        return Err(format!(
+            "error: dependency `{name}` missing Cork.toml at `{}`",
+            canonical.display()
+        ));
+    }
+    let dep_config_content = fs::read_to_string(&dep_cork_toml)
+        .map_err(|e| format!("Failed to read Cork.toml for dependency `{name}`: {e}"))?;
+    let dep_config: CorkConfig = toml::from_str(&dep_config_content)
+        .map_err(|e| format!("Failed to parse Cork.toml for dependency `{name}`: {e}"))?;
+
+    for (sub_name, sub_dep) in &dep_config.dependencies {
+        resolve_graph_recursive(
+            sub_name, sub_dep, &canonical, pins, git_targets, chain, visited, order,
+        )?;
+    }
+
+    chain.pop();
+
+    let declared_path = match dep {
+        Dependency::Path { path } => Some(path.clone()),
+        Dependency::Git { .. } => None,
+    };
+
+    visited.insert(
+        canonical.clone(),
+        ResolvedNode {
+            name: name.to_string(),
+            dir: canonical.clone(),
+            declared_path,
+            git_url,
+            commit,
+        },
+    );
+    order.push(canonical);
+
+    Ok(())
+}
+
+// Resolves the full transitive dependency graph declared in `config`,
+// deduplicating shared dependencies by their resolved directory and
+// returning them in topological (dependencies-before-dependents) order.
+// Reuses the git revisions pinned in Cork.lock when it's still valid for
+// `config_content`'s hash, and regenerates the lock otherwise. Pins (and
+// conflicting-ref detection) are keyed by git URL rather than by the
+// caller-chosen alias, since two unrelated dependencies can reuse the same
+// alias for different URLs.
+fn resolve_dependencies(
+    config: &CorkConfig,
+    cork_toml_path: &Path,
+    config_content: &str,
+) -> Result<Vec<ResolvedNode>, String> {
+    let toml_hash = lock::hash_hex(config_content);
+    let existing_lock = lock::load(cork_toml_path);
+    let lock_valid = existing_lock
+        .as_ref()
+        .is_some_and(|lock| lock.cork_toml_hash == toml_hash);
+
+    let pins: HashMap<String, String> = if lock_valid {
+        existing_lock
+            .unwrap()
+            .dependencies
+            .into_iter()
+            .filter_map(|d| d.git.clone().zip(d.commit))
+            .collect()
+    } else {
+        HashMap::new()
+    };
+
+    let mut visited = HashMap::new();
+    let mut order = Vec::new();
+    let mut chain = Vec::new();
+    let mut git_targets = HashMap::new();
+    let root_dir = cork_toml_path.parent().unwrap_or_else(|| Path::new("."));
+
+    for (name, dep) in &config.dependencies {
+        resolve_graph_recursive(
+            name,
+            dep,
+            root_dir,
+            &pins,
+            &mut git_targets,
+            &mut chain,
+            &mut visited,
+            &mut order,
+        )?;
+    }
+
+    let nodes: Vec<ResolvedNode> = order
+        .into_iter()
+        .map(|dir| visited.remove(&dir).unwrap())
+        .collect();
+
+    if !lock_valid {
+        let lockfile = lock::LockFile {
+            cork_toml_hash: toml_hash,
+            dependencies: nodes
+                .iter()
+                .map(|node| lock::LockedDependency {
+                    name: node.name.clone(),
+                    path: node
+                        .declared_path
+                        .clone()
+                        .unwrap_or_else(|| node.dir.display().to_string()),
+                    git: node.git_url.clone(),
+                    commit: node.commit.clone(),
+                })
+                .collect(),
+        };
+        lock::save(cork_toml_path, &lockfile)?;
+    }
+
+    Ok(nodes)
+}
+
+#[cfg(test)]
+mod graph_tests {
+    use super::*;
+    use crate::project::{CorkConfig, ProjectConfig};
+    use tempfile::tempdir;
+
+    fn write_cork_toml(dir: &Path, name: &str, deps: &[(&str, &str)]) {
+        let mut content = format!("[project]\nname = \"{name}\"\nversion = \"0.1.0\"\n\n[dependencies]\n");
+        for (alias, path) in deps {
+            content.push_str(&format!("{alias} = {{ path = \"{path}\" }}\n"));
+        }
+        fs::write(dir.join("Cork.toml"), content).unwrap();
+    }
+
+    fn path_config(deps: &[(&str, &str)]) -> CorkConfig {
+        CorkConfig {
+            project: ProjectConfig {
+                name: "root".to_string(),
+                version: "0.1.0".to_string(),
+            },
+            dependencies: deps
+                .iter()
+                .map(|(alias, path)| {
+                    (
+                        alias.to_string(),
+                        Dependency::Path {
+                            path: path.to_string(),
+                        },
+                    )
+                })
+                .collect(),
+            build: Default::default(),
+            profile: Default::default(),
+        }
+    }
+
+    #[test]
+    fn reused_alias_at_different_depth_is_not_a_false_cycle() {
+        let base = tempdir().expect("Failed to create temporary directory");
+        let dep_a = base.path().join("dep_a");
+        let dep_c = base.path().join("dep_c");
+        fs::create_dir_all(&dep_a).unwrap();
+        fs::create_dir_all(&dep_c).unwrap();
+        // dep_a depends on dep_c under the *same* alias ("utils") the root
+        // uses for dep_a itself, even though dep_c is a different directory.
+        write_cork_toml(&dep_a, "a", &[("utils", "../dep_c")]);
+        write_cork_toml(&dep_c, "c", &[]);
+
+        let config = path_config(&[("utils", "dep_a")]);
+        let cork_toml_path = base.path().join("Cork.toml");
+        let config_content = "[project]\nname = \"root\"\nversion = \"0.1.0\"\n";
+
+        let nodes = resolve_dependencies(&config, &cork_toml_path, config_content)
+            .expect("Alias reuse at a different depth must not be treated as a cycle");
+        assert_eq!(nodes.len(), 2);
+    }
+
+    #[test]
+    fn true_cycle_is_detected_even_with_different_aliases() {
+        let base = tempdir().expect("Failed to create temporary directory");
+        let dep_a = base.path().join("dep_a");
+        let dep_b = base.path().join("dep_b");
+        fs::create_dir_all(&dep_a).unwrap();
+        fs::create_dir_all(&dep_b).unwrap();
+        write_cork_toml(&dep_a, "a", &[("back", "../dep_b")]);
+        write_cork_toml(&dep_b, "b", &[("loop", "../dep_a")]);
+
+        let config = path_config(&[("first", "dep_a")]);
+        let cork_toml_path = base.path().join("Cork.toml");
+        let config_content = "[project]\nname = \"root\"\nversion = \"0.1.0\"\n";
+
+        let err = resolve_dependencies(&config, &cork_toml_path, config_content)
+            .expect_err("A real cycle must still be reported even with distinct aliases");
+        assert!(err.contains("Dependency cycle detected"), "{err}");
+    }
+
+    #[test]
+    fn shared_dependency_is_deduplicated_by_resolved_directory() {
+        let base = tempdir().expect("Failed to create temporary directory");
+        let dep_a = base.path().join("dep_a");
+        let dep_b = base.path().join("dep_b");
+        let shared = base.path().join("shared");
+        fs::create_dir_all(&dep_a).unwrap();
+        fs::create_dir_all(&dep_b).unwrap();
+        fs::create_dir_all(&shared).unwrap();
+        write_cork_toml(&dep_a, "a", &[("shared", "../shared")]);
+        write_cork_toml(&dep_b, "b", &[("shared", "../shared")]);
+        write_cork_toml(&shared, "shared", &[]);
+
+        let config = path_config(&[("a", "dep_a"), ("b", "dep_b")]);
+        let cork_toml_path = base.path().join("Cork.toml");
+        let config_content = "[project]\nname = \"root\"\nversion = \"0.1.0\"\n";
+
+        let nodes = resolve_dependencies(&config, &cork_toml_path, config_content)
+            .expect("Failed to resolve graph");
+        assert_eq!(nodes.len(), 3, "shared dependency must only appear once");
+    }
+
+    #[test]
+    fn lockfile_records_declared_path_not_a_canonicalized_one() {
+        let base = tempdir().expect("Failed to create temporary directory");
+        let dep_a = base.path().join("dep_a");
+        fs::create_dir_all(&dep_a).unwrap();
+        write_cork_toml(&dep_a, "a", &[]);
+
+        let config = path_config(&[("a", "dep_a")]);
+        let cork_toml_path = base.path().join("Cork.toml");
+        let config_content = "[project]\nname = \"root\"\nversion = \"0.1.0\"\n";
+
+        resolve_dependencies(&config, &cork_toml_path, config_content)
+            .expect("Failed to resolve graph");
+
+        let lockfile = lock::load(&cork_toml_path).expect("Failed to load Cork.lock");
+        assert_eq!(lockfile.dependencies[0].path, "dep_a");
+    }
+}
+
+// Parses a compiler-emitted .d file (Makefile-fragment format) and returns
+// the prerequisite paths listed after the `:` of the *first* rule. -MP emits
+// a separate phony-target line per header (e.g. `headers.h:`) after the
+// main rule; those must not be merged into the main rule's prerequisite list.
+fn parse_dep_file(dep_file: &Path) -> Result<Vec<PathBuf>, String> {
+    let content = fs::read_to_string(dep_file)
+        .map_err(|e| format!("Failed to read dependency file {}: {e}", dep_file.display()))?;
+
+    let logical_lines = join_continuation_lines(&content)
+        .map_err(|e| format!("{e} in {}", dep_file.display()))?;
+
+    let first_rule = logical_lines.first().map(String::as_str).unwrap_or("");
+    let prerequisites = first_rule.split_once(':').map(|(_, rest)| rest).unwrap_or("");
+
+    Ok(split_unescaped_whitespace(prerequisites)
+        .into_iter()
+        .map(PathBuf::from)
+        .collect())
+}
+
+// Splits `content` into logical lines, joining a physical line ending in a
+// trailing `\` with the next one rather than treating it as a line break.
+// Erroring if the very last line ends in a dangling `\`.
+fn join_continuation_lines(content: &str) -> Result<Vec<String>, String> {
+    let mut logical_lines = Vec::new();
+    let mut current = String::new();
+    let mut lines = content.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        match line.strip_suffix('\\') {
+            Some(stripped) => {
+                if lines.peek().is_none() {
+                    return Err("dangling `\\` continuation at end of file".to_string());
+                }
+                current.push_str(stripped);
+                current.push(' ');
+            }
+            None => {
+                current.push_str(line);
+                logical_lines.push(std::mem::take(&mut current));
+            }
+        }
+    }
+
+    Ok(logical_lines)
+}
+
+// Splits on whitespace, treating a backslash-escaped space as part of the
+// surrounding token rather than a separator.
+fn split_unescaped_whitespace(s: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut chars = s.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' && chars.peek().map(|n| n.is_whitespace()).unwrap_or(false) {
+            current.push(chars.next().unwrap());
+        } else if c.is_whitespace() {
+            if !current.is_empty() {
+                tokens.push(std::mem::take(&mut current));
+            }
+        } else {
+            current.push(c);
+        }
+    }
+
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    tokens
+}
+
+fn needs_recompile(src_file: &Path, obj_file: &Path) -> Result<bool, String> {
+    let src_time = src_file.metadata().and_then(|m| m.modified()).ok();
+    let obj_time = obj_file.metadata().and_then(|m| m.modified()).ok();
+
+    let (Some(src_time), Some(obj_time)) = (src_time, obj_time) else {
+        return Ok(true);
+    };
+
+    if src_time > obj_time {
+        return Ok(true);
+    }
+
+    let dep_file = obj_file.with_extension("d");
+    if !dep_file.exists() {
+        // No recorded dependency info yet (e.g. object predates this
+        // feature); recompile once so the `.d` file gets generated.
+        return Ok(true);
+    }
+
+    let deps = parse_dep_file(&dep_file)?;
+    Ok(deps.iter().any(|dep| {
+        dep.metadata()
+            .and_then(|m| m.modified())
+            .map(|dep_time| dep_time > obj_time)
+            .unwrap_or(true)
+    }))
+}
+
+#[cfg(test)]
+mod dep_file_tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn split_unescaped_whitespace_keeps_escaped_spaces_in_one_token() {
+        let tokens = split_unescaped_whitespace(r"foo\ bar.h baz.h");
+        assert_eq!(tokens, vec!["foo bar.h", "baz.h"]);
+    }
+
+    #[test]
+    fn join_continuation_lines_joins_trailing_backslash_into_one_logical_line() {
+        let logical_lines =
+            join_continuation_lines("main.o: main.c \\\n  headers.h\n").unwrap();
+        assert_eq!(logical_lines.len(), 1);
+        assert_eq!(logical_lines[0].trim(), "main.o: main.c    headers.h");
+    }
+
+    #[test]
+    fn join_continuation_lines_keeps_unrelated_lines_separate() {
+        // -MP emits a standalone phony-target line per header; it must not
+        // be merged into the preceding (unrelated) logical line.
+        let logical_lines =
+            join_continuation_lines("main.o: main.c headers.h\n\nheaders.h:\n").unwrap();
+        assert_eq!(logical_lines.len(), 3);
+        assert_eq!(logical_lines[0], "main.o: main.c headers.h");
+        assert_eq!(logical_lines[2], "headers.h:");
+    }
+
+    #[test]
+    fn join_continuation_lines_rejects_dangling_backslash() {
+        let result = join_continuation_lines("main.o: main.c \\\n");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn parse_dep_file_reads_prerequisites_after_colon() {
+        let dir = tempdir().expect("Failed to create temporary directory");
+        let dep_file = dir.path().join("main.d");
+        fs::write(&dep_file, "main.o: main.c headers.h \\\n  common.h\n").unwrap();
+
+        let deps = parse_dep_file(&dep_file).expect("Failed to parse dep file");
+        assert_eq!(
+            deps,
+            vec![
+                PathBuf::from("main.c"),
+                PathBuf::from("headers.h"),
+                PathBuf::from("common.h"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_dep_file_ignores_mp_phony_target_lines() {
+        let dir = tempdir().expect("Failed to create temporary directory");
+        let dep_file = dir.path().join("main.d");
+        fs::write(
+            &dep_file,
+            "main.o: main.c headers.h\n\nheaders.h:\n",
+        )
+        .unwrap();
+
+        let deps = parse_dep_file(&dep_file).expect("Failed to parse dep file");
+        assert_eq!(
+            deps,
+            vec![PathBuf::from("main.c"), PathBuf::from("headers.h")]
+        );
+    }
+}
+
+pub fn build_project(release: bool, jobs: Option<usize>) -> Result<PathBuf, String> {
     let cork_toml_path = Path::new("Cork.toml");
 
     if !cork_toml_path.exists() {
@@ -35,24 +908,20 @@ pub fn build_project(release: bool) -> Result<PathBuf, String> {
 
     create_all(&obj_dir, true).map_err(|e| format!("Failed to create obj directory: {e}"))?;
 
+    let profile = resolve_profile(&config, release);
+
     // Collect include dirs (start with project's own)
     let mut include_dirs = vec![
         public_include_dir.to_owned(),
         private_include_dir.to_owned(),
     ];
     let mut all_objects_to_link = Vec::new();
+    let mut compile_jobs = Vec::new();
 
-    // Build dependencies
-    for (dep_name, dep) in &config.dependencies {
-        let dep_path = Path::new(&dep.path);
-        let dep_cork_toml = dep_path.join("Cork.toml");
-        if !dep_cork_toml.exists() {
-            return Err(format!(
-                "error: dependency `{dep_name}` missing Cork.toml at `{}`",
-                dep_path.display()
-            ));
-        }
-
+    // Build dependencies, in topological order, pinned by Cork.lock
+    let resolved_deps = resolve_dependencies(&config, cork_toml_path, &config_content)?;
+    for node in &resolved_deps {
+        let dep_path = &node.dir;
         let dep_src_dir = dep_path.join("src");
         let dep_public_include_dir = dep_path.join("include");
         let dep_obj_dir = dep_path.join("build").join(build_subdir).join("obj");
@@ -60,6 +929,11 @@ pub fn build_project(release: bool) -> Result<PathBuf, String> {
         create_all(&dep_obj_dir, true)
             .map_err(|e| format!("Failed to create dependency obj directory: {e}"))?;
 
+        // Merge this (and every earlier, already-resolved) reachable
+        // dependency's public headers into the include path before
+        // compiling, so transitive deps can see each other's headers.
+        include_dirs.push(dep_public_include_dir);
+
         let dep_source_files: Vec<_> = fs::read_dir(&dep_src_dir)
             .map_err(|e| format!("Failed to read dependency src directory: {e}"))?
             .filter_map(Result::ok)
@@ -67,13 +941,6 @@ pub fn build_project(release: bool) -> Result<PathBuf, String> {
             .map(|entry| entry.path())
             .collect();
 
-        let dep_header_files: Vec<_> = fs::read_dir(&dep_public_include_dir)
-            .map_err(|e| format!("Failed to read dependency include directory: {e}"))?
-            .filter_map(Result::ok)
-            .filter(|entry| entry.path().extension().and_then(|e| e.to_str()) == Some("h"))
-            .map(|entry| entry.path())
-            .collect();
-
         for dep_src_file in &dep_source_files {
             let dep_obj_file = dep_obj_dir.join(
                 dep_src_file
@@ -84,46 +951,15 @@ pub fn build_project(release: bool) -> Result<PathBuf, String> {
             );
             all_objects_to_link.push(dep_obj_file.clone());
 
-            let src_time = dep_src_file.metadata().and_then(|m| m.modified()).ok();
-            let obj_time = dep_obj_file.metadata().and_then(|m| m.modified()).ok();
-
-            let needs_compile = src_time.map_or(true, |st| {
-                obj_time.map_or(true, |ot| {
-                    st > ot
-                        || dep_header_files.iter().any(|h| {
-                            h.metadata()
-                                .and_then(|m| m.modified())
-                                .map(|ht| ht > ot)
-                                .unwrap_or(true)
-                        })
-                })
-            });
-
-            if needs_compile {
-                let mut cmd = Command::new("gcc");
-                cmd.arg("-c")
-                    .arg(dep_src_file)
-                    .arg("-o")
-                    .arg(&dep_obj_file)
-                    .arg("-I")
-                    .arg(&dep_public_include_dir); // Dependency’s public headers
-
-                if release {
-                    cmd.arg("-O3");
-                }
-
-                let output = cmd
-                    .output()
-                    .map_err(|e| format!("Failed to compile dependency {dep_src_file:?}: {e}"))?;
-                if !output.status.success() {
-                    let stderr = String::from_utf8_lossy(&output.stderr);
-                    return Err(format!(
-                        "Compilation failed for {dep_src_file:?}:\n{stderr}"
-                    ));
-                }
+            if needs_recompile(dep_src_file, &dep_obj_file)? {
+                compile_jobs.push(CompileJob {
+                    src_file: dep_src_file.clone(),
+                    obj_file: dep_obj_file,
+                    include_dirs: include_dirs.clone(),
+                    profile: profile.clone(),
+                });
             }
         }
-        include_dirs.push(dep_public_include_dir); // Add dependency’s public headers to include path
     }
 
     // Build main project
@@ -138,20 +974,6 @@ pub fn build_project(release: bool) -> Result<PathBuf, String> {
         return Err("No source files found in src directory!".to_string());
     }
 
-    let public_headers: Vec<_> = fs::read_dir(public_include_dir)
-        .map_err(|e| format!("Failed to read include directory: {e}"))?
-        .filter_map(Result::ok)
-        .filter(|entry| entry.path().extension().and_then(|e| e.to_str()) == Some("h"))
-        .map(|entry| entry.path())
-        .collect();
-
-    let private_headers: Vec<_> = fs::read_dir(private_include_dir)
-        .map_err(|e| format!("Failed to read src/include directory: {e}"))?
-        .filter_map(Result::ok)
-        .filter(|entry| entry.path().extension().and_then(|e| e.to_str()) == Some("h"))
-        .map(|entry| entry.path())
-        .collect();
-
     let mut needs_link = !output_executable.exists();
 
     for src_file in &source_files {
@@ -164,52 +986,29 @@ pub fn build_project(release: bool) -> Result<PathBuf, String> {
         );
         all_objects_to_link.push(obj_file.clone());
 
-        let src_time = src_file.metadata().and_then(|m| m.modified()).ok();
-        let obj_time = obj_file.metadata().and_then(|m| m.modified()).ok();
-
-        let needs_compile = src_time.map_or(true, |st| {
-            obj_time.map_or(true, |ot| {
-                st > ot
-                    || public_headers
-                        .iter()
-                        .chain(private_headers.iter())
-                        .any(|h| {
-                            h.metadata()
-                                .and_then(|m| m.modified())
-                                .map(|ht| ht > ot)
-                                .unwrap_or(true)
-                        })
-            })
-        });
-
-        if needs_compile {
-            let mut cmd = Command::new("gcc");
-            cmd.arg("-c").arg(src_file).arg("-o").arg(&obj_file);
-            for inc in &include_dirs {
-                cmd.arg("-I").arg(inc); // Include all public headers (own + dependencies)
-            }
-
-            if release {
-                cmd.arg("-O3");
-            }
-
-            let output = cmd
-                .output()
-                .map_err(|e| format!("Failed to compile {src_file:?}: {e}"))?;
-            if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                return Err(stderr.to_string());
-            }
+        if needs_recompile(src_file, &obj_file)? {
+            compile_jobs.push(CompileJob {
+                src_file: src_file.clone(),
+                obj_file,
+                include_dirs: include_dirs.clone(), // Include all public headers (own + dependencies)
+                profile: profile.clone(),
+            });
             needs_link = true;
         }
     }
 
+    let job_count = jobs.unwrap_or_else(default_job_count);
+    compile_all(compile_jobs, job_count)?;
+
     if needs_link {
-        let mut cmd = Command::new("gcc");
+        let mut cmd = Command::new(&profile.compiler);
         cmd.arg("-o").arg(&output_executable);
         for obj in &all_objects_to_link {
             cmd.arg(obj);
         }
+        for flag in &profile.ldflags {
+            cmd.arg(flag);
+        }
 
         let output = cmd.output().map_err(|e| format!("Failed to link: {e}"))?;
         if !output.status.success() {
@@ -221,8 +1020,8 @@ pub fn build_project(release: bool) -> Result<PathBuf, String> {
     Ok(output_executable)
 }
 
-pub fn run_project(release: bool) -> Result<(), String> {
-    let executable_path = build_project(release)?;
+pub fn run_project(release: bool, jobs: Option<usize>) -> Result<(), String> {
+    let executable_path = build_project(release, jobs)?;
 
     let status = Command::new(executable_path)
         .status()
@@ -236,3 +1035,154 @@ pub fn run_project(release: bool) -> Result<(), String> {
     }
     Ok(())
 }
+
+// Builds and runs every .c file under tests/ as its own executable, linked
+// against the project's own object files (everything but main.o).
+pub fn test_project(release: bool) -> Result<(), String> {
+    build_project(release, None)?;
+
+    let cork_toml_path = Path::new("Cork.toml");
+    let config_content =
+        fs::read_to_string(cork_toml_path).map_err(|e| format!("Failed to read Cork.toml: {e}"))?;
+    let config: CorkConfig =
+        toml::from_str(&config_content).map_err(|e| format!("Failed to parse Cork.toml: {e}"))?;
+
+    let tests_dir = Path::new("tests");
+    if !tests_dir.exists() {
+        return Err("No tests directory found!".to_string());
+    }
+
+    let public_include_dir = Path::new("include");
+    let private_include_dir = Path::new("src/include");
+    let build_dir = Path::new("build");
+    let build_subdir = if release { "release" } else { "debug" };
+    let obj_dir = build_dir.join(build_subdir).join("obj");
+    let test_bin_dir = build_dir.join(build_subdir).join("tests");
+    let test_obj_dir = test_bin_dir.join("obj");
+
+    create_all(&test_obj_dir, true)
+        .map_err(|e| format!("Failed to create test obj directory: {e}"))?;
+
+    let profile = resolve_profile(&config, release);
+
+    let mut include_dirs = vec![
+        public_include_dir.to_owned(),
+        private_include_dir.to_owned(),
+    ];
+
+    // Everything the main project compiled except main.o, so tests can
+    // pull in the project's own library code.
+    let mut library_objects: Vec<PathBuf> = fs::read_dir(&obj_dir)
+        .map_err(|e| format!("Failed to read obj directory: {e}"))?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("o"))
+        .filter(|path| path.file_name().and_then(|n| n.to_str()) != Some("main.o"))
+        .collect();
+
+    for node in &resolve_dependencies(&config, cork_toml_path, &config_content)? {
+        let dep_public_include_dir = node.dir.join("include");
+        let dep_obj_dir = node.dir.join("build").join(build_subdir).join("obj");
+
+        include_dirs.push(dep_public_include_dir);
+
+        if dep_obj_dir.exists() {
+            let dep_objects: Vec<_> = fs::read_dir(&dep_obj_dir)
+                .map_err(|e| format!("Failed to read dependency obj directory: {e}"))?
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("o"))
+                .collect();
+            library_objects.extend(dep_objects);
+        }
+    }
+
+    let test_files: Vec<_> = fs::read_dir(tests_dir)
+        .map_err(|e| format!("Failed to read tests directory: {e}"))?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().extension().and_then(|e| e.to_str()) == Some("c"))
+        .map(|entry| entry.path())
+        .collect();
+
+    if test_files.is_empty() {
+        return Err("No test files found in tests directory!".to_string());
+    }
+
+    let mut failed_tests = Vec::new();
+
+    for test_file in &test_files {
+        let test_name = test_file
+            .file_stem()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let test_obj_file = test_obj_dir.join(format!("{test_name}.o"));
+        let test_executable = test_bin_dir.join(&test_name);
+
+        let mut compile_cmd = Command::new(&profile.compiler);
+        compile_cmd
+            .arg("-c")
+            .arg(test_file)
+            .arg("-o")
+            .arg(&test_obj_file);
+        for inc in &include_dirs {
+            compile_cmd.arg("-I").arg(inc);
+        }
+        if profile.debug_symbols {
+            compile_cmd.arg("-g");
+        }
+        if let Some(opt_flag) = &profile.opt_flag {
+            compile_cmd.arg(opt_flag);
+        }
+        for flag in &profile.cflags {
+            compile_cmd.arg(flag);
+        }
+
+        let output = compile_cmd
+            .output()
+            .map_err(|e| format!("Failed to compile test {test_file:?}: {e}"))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Compilation failed for {test_file:?}:\n{stderr}"));
+        }
+
+        let mut link_cmd = Command::new(&profile.compiler);
+        link_cmd.arg("-o").arg(&test_executable).arg(&test_obj_file);
+        for obj in &library_objects {
+            link_cmd.arg(obj);
+        }
+        for flag in &profile.ldflags {
+            link_cmd.arg(flag);
+        }
+
+        let output = link_cmd
+            .output()
+            .map_err(|e| format!("Failed to link test `{test_name}`: {e}"))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("Linking failed for test `{test_name}`:\n{stderr}"));
+        }
+
+        println!("   {} {test_name}", "Running".green());
+        let status = Command::new(&test_executable)
+            .status()
+            .map_err(|e| format!("Failed to run test `{test_name}`: {e}"))?;
+
+        if status.success() {
+            println!("   {} {test_name}", "Passed".green());
+        } else {
+            println!("   {} {test_name}", "Failed".red());
+            failed_tests.push(test_name);
+        }
+    }
+
+    if !failed_tests.is_empty() {
+        return Err(format!(
+            "{} test binary(ies) failed: {}",
+            failed_tests.len(),
+            failed_tests.join(", ")
+        ));
+    }
+
+    Ok(())
+}
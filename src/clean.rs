@@ -2,6 +2,8 @@ use std::fs;
 use std::path::Path;
 use walkdir::WalkDir;
 
+// Cork.lock lives next to Cork.toml, not under build/, so it is left
+// untouched here and the next build still reuses its pinned revisions.
 pub fn clean_project() -> Result<(), String> {
     let build_dir = Path::new("build");
 
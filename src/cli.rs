@@ -20,13 +20,25 @@ pub enum Commands {
     Build {
         #[arg(long)]
         release: bool,
+        /// Number of objects to compile in parallel (defaults to the number of logical CPUs)
+        #[arg(short = 'j', long)]
+        jobs: Option<usize>,
     },
     /// Builds and runs the C project
     #[command(alias = "r")]
     Run {
         #[arg(long)]
         release: bool,
+        /// Number of objects to compile in parallel (defaults to the number of logical CPUs)
+        #[arg(short = 'j', long)]
+        jobs: Option<usize>,
     },
     /// Cleans the build directory
     Clean,
+    /// Builds and runs the test suite
+    #[command(alias = "t")]
+    Test {
+        #[arg(long)]
+        release: bool,
+    },
 }
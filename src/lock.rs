@@ -0,0 +1,96 @@
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+
+// One resolved node of the dependency graph, as recorded in Cork.lock.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LockedDependency {
+    pub name: String,
+    pub path: String,
+    #[serde(default)]
+    pub git: Option<String>,
+    #[serde(default)]
+    pub commit: Option<String>,
+}
+
+// The full resolved dependency graph, pinned to the Cork.toml it was
+// generated from via cork_toml_hash. A hash mismatch means Cork.toml
+// changed since the lock was written and the graph must be re-resolved.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct LockFile {
+    pub cork_toml_hash: String,
+    #[serde(default)]
+    pub dependencies: Vec<LockedDependency>,
+}
+
+// Hashes arbitrary content into a stable hex string, used both to key the
+// git dependency cache and to detect Cork.toml changes here.
+pub fn hash_hex(content: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+// Loads Cork.lock next to cork_toml_path, if present and parseable. A
+// missing or corrupt lockfile is treated as "no lock yet" rather than an
+// error, since it just means the graph will be resolved from scratch.
+pub fn load(cork_toml_path: &Path) -> Option<LockFile> {
+    let lock_path = lock_path_for(cork_toml_path);
+    let content = fs::read_to_string(lock_path).ok()?;
+    toml::from_str(&content).ok()
+}
+
+pub fn save(cork_toml_path: &Path, lockfile: &LockFile) -> Result<(), String> {
+    let lock_path = lock_path_for(cork_toml_path);
+    let content =
+        toml::to_string_pretty(lockfile).map_err(|e| format!("Failed to serialize Cork.lock: {e}"))?;
+    fs::write(lock_path, content).map_err(|e| format!("Failed to write Cork.lock: {e}"))
+}
+
+fn lock_path_for(cork_toml_path: &Path) -> std::path::PathBuf {
+    cork_toml_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("Cork.lock")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn hash_hex_is_stable_and_content_sensitive() {
+        assert_eq!(hash_hex("a"), hash_hex("a"));
+        assert_ne!(hash_hex("a"), hash_hex("b"));
+    }
+
+    #[test]
+    fn save_then_load_round_trips() {
+        let dir = tempdir().expect("Failed to create temporary directory");
+        let cork_toml_path = dir.path().join("Cork.toml");
+
+        let lockfile = LockFile {
+            cork_toml_hash: hash_hex("[project]\nname = \"x\"\n"),
+            dependencies: vec![LockedDependency {
+                name: "libfoo".to_string(),
+                path: "../libfoo".to_string(),
+                git: None,
+                commit: None,
+            }],
+        };
+        save(&cork_toml_path, &lockfile).expect("Failed to save lockfile");
+
+        let loaded = load(&cork_toml_path).expect("Failed to load lockfile");
+        assert_eq!(loaded.cork_toml_hash, lockfile.cork_toml_hash);
+        assert_eq!(loaded.dependencies[0].path, "../libfoo");
+    }
+
+    #[test]
+    fn load_missing_lockfile_returns_none() {
+        let dir = tempdir().expect("Failed to create temporary directory");
+        assert!(load(&dir.path().join("Cork.toml")).is_none());
+    }
+}
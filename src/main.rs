@@ -3,6 +3,7 @@ use clap::Parser;
 mod build;
 mod clean;
 mod cli;
+mod lock;
 mod project;
 
 use cli::{Cli, Commands};
@@ -12,9 +13,10 @@ fn main() {
 
     let result = match cli.command {
         Commands::New { name } => project::create_new_project(&name),
-        Commands::Build { release } => build::build_project(release).map(|_| ()),
-        Commands::Run { release } => build::run_project(release),
+        Commands::Build { release, jobs } => build::build_project(release, jobs).map(|_| ()),
+        Commands::Run { release, jobs } => build::run_project(release, jobs),
         Commands::Clean => clean::clean_project(),
+        Commands::Test { release } => build::test_project(release),
     };
 
     if let Err(e) = result {
@@ -55,7 +57,7 @@ mod tests {
             .expect("Failed to create project for build test");
         std::env::set_current_dir(&project_path).expect("Failed to change to project directory");
 
-        let result = build::build_project(false);
+        let result = build::build_project(false, None);
         assert!(result.is_ok(), "Build failed: {:?}", result);
 
         assert!(
@@ -78,7 +80,7 @@ mod tests {
             .expect("Failed to create project");
         std::env::set_current_dir(&project_path).expect("Failed to change to project directory");
 
-        build::build_project(false).expect("Initial build failed");
+        build::build_project(false, None).expect("Initial build failed");
         let initial_obj_time = fs::metadata("build/debug/obj/main.o")
             .and_then(|m| m.modified())
             .expect("Failed to get initial obj time");
@@ -86,7 +88,7 @@ mod tests {
             .and_then(|m| m.modified())
             .expect("Failed to get initial exe time");
 
-        build::build_project(false).expect("Second build failed");
+        build::build_project(false, None).expect("Second build failed");
         let second_obj_time = fs::metadata("build/debug/obj/main.o")
             .and_then(|m| m.modified())
             .expect("Failed to get second obj time");
@@ -109,7 +111,7 @@ mod tests {
             .expect("Failed to open main.c");
         writeln!(file, "\n// Modified").expect("Failed to modify main.c");
 
-        build::build_project(false).expect("Third build failed");
+        build::build_project(false, None).expect("Third build failed");
         let third_obj_time = fs::metadata("build/debug/obj/main.o")
             .and_then(|m| m.modified())
             .expect("Failed to get third obj time");
@@ -130,12 +132,31 @@ mod tests {
             .expect("Failed to create project for run test");
         std::env::set_current_dir(&project_path).expect("Failed to change to project directory");
 
-        build::build_project(false).expect("Failed to build project for run test");
+        build::build_project(false, None).expect("Failed to build project for run test");
 
-        let result = build::run_project(false);
+        let result = build::run_project(false, None);
         assert!(result.is_ok(), "Run failed: {:?}", result);
     }
 
+    #[test]
+    fn test_test_project() {
+        let temp_dir = tempdir().expect("Failed to create temporary directory");
+        let test_project_name = "test_subcommand_test";
+        let project_path = temp_dir.path().join(test_project_name);
+
+        project::create_new_project(&project_path.to_string_lossy())
+            .expect("Failed to create project for test subcommand test");
+        std::env::set_current_dir(&project_path).expect("Failed to change to project directory");
+
+        let result = build::test_project(false);
+        assert!(result.is_ok(), "cork test failed: {:?}", result);
+
+        assert!(
+            Path::new("build/debug/tests/test_main").exists(),
+            "Test executable not created"
+        );
+    }
+
     #[test]
     fn test_clean_project() {
         let temp_dir = tempdir().expect("Failed to create temporary directory");
@@ -146,7 +167,7 @@ mod tests {
             .expect("Failed to create project for clean test");
         std::env::set_current_dir(&project_path).expect("Failed to change to project directory");
 
-        build::build_project(false).expect("Failed to build project for clean test");
+        build::build_project(false, None).expect("Failed to build project for clean test");
 
         assert!(
             Path::new("build").exists(),
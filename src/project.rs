@@ -11,6 +11,10 @@ pub struct CorkConfig {
     pub project: ProjectConfig,
     #[serde(default)] // Empty map if no dependencies section
     pub dependencies: HashMap<String, Dependency>,
+    #[serde(default)] // gcc with no extra flags if no [build] section
+    pub build: BuildConfig,
+    #[serde(default)] // today's defaults if no [profile.*] sections
+    pub profile: ProfilesConfig,
 }
 
 #[derive(Deserialize)]
@@ -19,9 +23,52 @@ pub struct ProjectConfig {
     pub version: String,
 }
 
+// Toolchain-wide settings, shared by every profile. Overridden at build
+// time by the CC and CFLAGS environment variables.
+#[derive(Deserialize, Default)]
+pub struct BuildConfig {
+    pub compiler: Option<String>,
+    #[serde(default)]
+    pub cflags: Vec<String>,
+    #[serde(default)]
+    pub ldflags: Vec<String>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct ProfilesConfig {
+    #[serde(default)]
+    pub debug: ProfileConfig,
+    #[serde(default)]
+    pub release: ProfileConfig,
+}
+
+// Per-profile overrides layered on top of [build]. opt_level becomes
+// -O<opt_level>; debug_symbols defaults to true for debug, false for release.
+#[derive(Deserialize, Default)]
+pub struct ProfileConfig {
+    pub opt_level: Option<String>,
+    #[serde(default)]
+    pub cflags: Vec<String>,
+    #[serde(default)]
+    pub ldflags: Vec<String>,
+    pub debug_symbols: Option<bool>,
+}
+
 #[derive(Deserialize)]
-pub struct Dependency {
-    pub path: String, // For now, only local paths; can extend to Git later
+#[serde(untagged)]
+pub enum Dependency {
+    Path {
+        path: String,
+    },
+    Git {
+        git: String,
+        #[serde(default)]
+        branch: Option<String>,
+        #[serde(default)]
+        tag: Option<String>,
+        #[serde(default)]
+        rev: Option<String>,
+    },
 }
 
 pub fn create_new_project(name: &str) -> Result<(), String> {
@@ -74,6 +121,17 @@ name = "{name}"
 version = "0.1.0"
 
 [dependencies]
+
+# [build]
+# compiler = "clang"
+# cflags = []
+# ldflags = []
+
+# [profile.debug]
+# opt_level = "0"
+
+# [profile.release]
+# opt_level = "3"
 "#
     );
     fs::write(project_dir.join("Cork.toml"), &cork_toml).map_err(|e| e.to_string())?;